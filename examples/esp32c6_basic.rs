@@ -10,7 +10,7 @@
 //!
 //! # Usage
 //! ```bash
-//! cargo run --example esp32c6_basic --release
+//! cargo run --example esp32c6_basic --features esp32c6-examples --release
 //! ```
 
 
@@ -5,12 +5,14 @@
 //!
 //! # Usage
 //! ```bash
-//! cargo run --example esp32c6_counter --release
+//! cargo run --example esp32c6_counter --features esp32c6-examples --release
 //! ```
 
 #![no_std]
 #![no_main]
 
+use core::fmt::Write as _;
+
 use {esp_backtrace as _, esp_println as _};
 use esp_hal::{clock::CpuClock, delay::Delay, i2c::master::{Config, I2c}};
 use grove_lcd_rgb::GroveLcd;
@@ -43,41 +45,8 @@ fn main() -> ! {
     loop {
         // Update counter on second line
         lcd.set_cursor(0, 1).unwrap();
-        
-        // Format counter (simple no_std approach)
-        let mut buffer = [0u8; 16];
-        let mut pos = 0;
-        let mut n = counter;
-        
-        if n == 0 {
-            buffer[pos] = b'0';
-            pos += 1;
-        } else {
-            let mut divisor = 1_000_000_000;
-            let mut started = false;
-            
-            while divisor > 0 {
-                let digit = (n / divisor) as u8;
-                if digit > 0 || started {
-                    buffer[pos] = b'0' + digit;
-                    pos += 1;
-                    started = true;
-                }
-                n %= divisor;
-                divisor /= 10;
-            }
-        }
-        
-        // Print counter
-        for i in 0..pos {
-            lcd.write(buffer[i]).unwrap();
-        }
-        
-        // Clear rest of line
-        for _ in pos..16 {
-            lcd.write(b' ').unwrap();
-        }
-        
+        write!(lcd, "{:<16}", counter).ok();
+
         // Change color every 10 counts
         let phase = (counter / 10) % 6;
         match phase {
@@ -0,0 +1,681 @@
+//! Driver for the Seeed Studio Grove LCD RGB Backlight display.
+//!
+//! The module is a 16x2 (or larger, up to 20x4) character LCD
+//! (HD44780-compatible text controller at I2C address `0x3E`) paired with an
+//! RGB backlight. Two hardware revisions exist: v4 boards use a PCA9633-style
+//! LED controller at `0x62`, v5 boards use a different controller at `0x30`.
+//! [`GroveLcd::begin`] probes for both and [`GroveLcd::rgb_variant`] reports
+//! which one (if either) was found; see [`RgbVariant`].
+//!
+//! [`GroveLcd`] is generic over any [`embedded_hal::i2c::I2c`] and
+//! [`embedded_hal::delay::DelayNs`] implementation, so it runs unmodified on
+//! esp-hal, STM32 (`BlockingI2c`), esp-idf-hal, or any other HAL that
+//! implements embedded-hal 1.0, and can share a bus with other devices via
+//! `embedded-hal-bus`.
+//!
+//! # Example
+//! ```ignore
+//! let mut lcd = GroveLcd::new(i2c, delay);
+//! lcd.begin(16, 2)?;
+//! lcd.set_rgb(0, 255, 0)?;
+//! lcd.print("Hello, World!")?;
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+use embedded_hal::delay::DelayNs;
+
+/// I2C address of the HD44780-compatible text controller.
+const LCD_ADDR: u8 = 0x3E;
+/// I2C address of the PCA9633-style RGB backlight controller on v4 boards.
+const RGB_ADDR_V4: u8 = 0x62;
+/// I2C address of the RGB backlight controller on v5 boards.
+const RGB_ADDR_V5: u8 = 0x30;
+
+/// Control byte prefix for a command sent to the text controller.
+const CMD_PREFIX: u8 = 0x80;
+/// Control byte prefix for data sent to the text controller.
+const DATA_PREFIX: u8 = 0x40;
+
+/// Display control bit: display on.
+const DISPLAY_ON: u8 = 0x04;
+/// Display control bit: cursor on.
+const CURSOR_ON: u8 = 0x02;
+/// Display control bit: cursor blink on.
+const BLINK_ON: u8 = 0x01;
+
+/// Entry mode bit: increment cursor position (left-to-right text direction).
+const ENTRY_INCREMENT: u8 = 0x02;
+/// Entry mode bit: shift the whole display instead of the cursor as text is
+/// entered.
+const ENTRY_SHIFT: u8 = 0x01;
+
+/// v4 PCA9633 register addresses used for hardware backlight blink/breathe.
+const V4_REG_MODE2: u8 = 0x01;
+const V4_REG_LEDOUT: u8 = 0x08;
+const V4_REG_GRPFREQ: u8 = 0x06;
+const V4_REG_GRPPWM: u8 = 0x07;
+/// v5 PWM enable register, also reused to approximate backlight effects.
+const V5_REG_PWM: u8 = 0x04;
+/// v5 PWM value that leaves all LEDs always on (set during `begin`).
+const V5_PWM_ALWAYS_ON: u8 = 0x15;
+
+/// MODE2 bit selecting group blinking (vs. group dimming) mode.
+const MODE2_DMBLNK: u8 = 0x20;
+/// LEDOUT value driving all four channels from individual PWM only, the
+/// normal steady-color mode set up by [`GroveLcd::begin`].
+const LEDOUT_INDIVIDUAL_PWM: u8 = 0xAA;
+/// LEDOUT value driving all four channels from individual PWM *and* the
+/// group blink/dim controls, required for `GRPFREQ`/`GRPPWM` to take effect.
+const LEDOUT_GROUP_CONTROLLED: u8 = 0xFF;
+
+/// Largest display geometry the shadow framebuffer is sized for (20x4 is the
+/// biggest common HD44780 character display).
+const MAX_COLS: usize = 20;
+const MAX_ROWS: usize = 4;
+const MAX_CELLS: usize = MAX_COLS * MAX_ROWS;
+
+/// Errors returned by [`GroveLcd`].
+#[derive(Debug)]
+pub enum Error<E> {
+    /// The underlying I2C transaction failed.
+    I2c(E),
+    /// Neither the v4 (`0x62`) nor v5 (`0x30`) RGB backlight controller
+    /// address responded during [`GroveLcd::begin`].
+    RgbControllerNotFound,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(e: E) -> Self {
+        Error::I2c(e)
+    }
+}
+
+/// RGB backlight controller hardware revision, detected by [`GroveLcd::begin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RgbVariant {
+    /// PCA9633-style controller at `0x62`, found on v4 boards.
+    V4,
+    /// Controller at `0x30`, found on v5 boards.
+    V5,
+}
+
+/// Driver for the Grove LCD RGB Backlight display.
+pub struct GroveLcd<I2C, D> {
+    i2c: I2C,
+    delay: D,
+    cols: u8,
+    rows: u8,
+    /// Current display control flags (display/cursor/blink), without the
+    /// `0x08` command base.
+    display_control: u8,
+    /// Current entry mode flags (direction/autoscroll), without the `0x04`
+    /// command base.
+    entry_mode: u8,
+    /// Column of the virtual cursor: set by [`GroveLcd::set_cursor`] and
+    /// advanced by each `write`. Used to restore the DDRAM address after a
+    /// CGRAM write.
+    cursor_col: u8,
+    /// Row of the virtual cursor.
+    cursor_row: u8,
+    /// RGB backlight controller most recently detected by
+    /// [`GroveLcd::begin`], if any.
+    rgb_variant: Option<RgbVariant>,
+    /// Desired contents of each cell, row-major, indexed by `row * cols +
+    /// col`. Only the first `cols * rows` entries are meaningful.
+    shadow: [u8; MAX_CELLS],
+    /// Contents last written to the physical display; compared against
+    /// `shadow` on [`GroveLcd::flush`] to find what changed.
+    displayed: [u8; MAX_CELLS],
+    /// Inclusive `(min, max)` index range that may differ between `shadow`
+    /// and `displayed`, or `None` if nothing is pending.
+    dirty: Option<(usize, usize)>,
+    /// DDRAM address the hardware cursor is known to sit at, or `None` if
+    /// unknown (forcing the next positioned write to reissue `set_cursor`).
+    hw_cursor: Option<u8>,
+    /// When `true` (the default), `print`/`write`/`set_cursor` write straight
+    /// through to the display. When `false`, they only update the shadow
+    /// buffer until [`GroveLcd::flush`] is called.
+    auto_flush: bool,
+}
+
+impl<I2C, D> GroveLcd<I2C, D>
+where
+    I2C: embedded_hal::i2c::I2c,
+    D: DelayNs,
+{
+    /// Wrap an already-configured I2C bus and delay source.
+    ///
+    /// Call [`GroveLcd::begin`] before using the display.
+    pub fn new(i2c: I2C, delay: D) -> Self {
+        Self {
+            i2c,
+            delay,
+            cols: 16,
+            rows: 2,
+            display_control: DISPLAY_ON,
+            entry_mode: ENTRY_INCREMENT,
+            cursor_col: 0,
+            cursor_row: 0,
+            rgb_variant: None,
+            shadow: [b' '; MAX_CELLS],
+            displayed: [b' '; MAX_CELLS],
+            dirty: None,
+            hw_cursor: None,
+            auto_flush: true,
+        }
+    }
+
+    /// Initialize the text controller and RGB backlight for a `cols` x `rows`
+    /// display.
+    pub fn begin(&mut self, cols: u8, rows: u8) -> Result<(), Error<I2C::Error>> {
+        self.cols = cols;
+        self.rows = rows;
+
+        // Text controller power-on sequence.
+        self.delay.delay_ms(50);
+        self.send_command(0x38)?; // Function set: 8-bit, 2-line, 5x8 font
+        self.delay.delay_ms(5);
+        self.display_control = DISPLAY_ON;
+        self.send_command(0x08 | self.display_control)?; // Display on, cursor off, blink off
+        self.delay.delay_ms(5);
+        self.entry_mode = ENTRY_INCREMENT;
+        self.send_command(0x04 | self.entry_mode)?; // Left-to-right, no autoscroll
+        self.delay.delay_ms(5);
+        self.clear()?;
+
+        // RGB backlight controller power-on sequence; the two hardware
+        // revisions live at different addresses and have unrelated register
+        // maps, so probe for whichever is present.
+        let variant = self.detect_rgb_variant()?;
+        self.rgb_variant = Some(variant);
+        match variant {
+            RgbVariant::V4 => {
+                self.i2c.write(RGB_ADDR_V4, &[0x00, 0x00])?; // MODE1: normal mode
+                self.i2c.write(RGB_ADDR_V4, &[0x01, 0x00])?; // MODE2: default
+                self.i2c.write(RGB_ADDR_V4, &[0x08, 0xAA])?; // LEDOUT: individual PWM for all channels
+            }
+            RgbVariant::V5 => {
+                self.i2c.write(RGB_ADDR_V5, &[0x00, 0x07])?; // Reset all
+                self.i2c.write(RGB_ADDR_V5, &[0x04, 0x15])?; // PWM enable, all LEDs always on
+            }
+        }
+        self.set_rgb(0xFF, 0xFF, 0xFF)?;
+
+        Ok(())
+    }
+
+    /// Probe the v4 (`0x62`) and v5 (`0x30`) RGB controller addresses with a
+    /// zero-length write, returning whichever one responds.
+    fn detect_rgb_variant(&mut self) -> Result<RgbVariant, Error<I2C::Error>> {
+        if self.i2c.write(RGB_ADDR_V4, &[]).is_ok() {
+            return Ok(RgbVariant::V4);
+        }
+        if self.i2c.write(RGB_ADDR_V5, &[]).is_ok() {
+            return Ok(RgbVariant::V5);
+        }
+        Err(Error::RgbControllerNotFound)
+    }
+
+    /// The RGB backlight controller revision most recently detected by
+    /// [`GroveLcd::begin`], or `None` if no `begin` call has detected one.
+    pub fn rgb_variant(&self) -> Option<RgbVariant> {
+        self.rgb_variant
+    }
+
+    /// Clear the display and return the cursor to the home position.
+    ///
+    /// This always writes straight through to the display, regardless of
+    /// [`GroveLcd::auto_flush`], and resets the shadow buffer to match.
+    pub fn clear(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.send_command(0x01)?;
+        self.delay.delay_ms(2);
+        self.shadow = [b' '; MAX_CELLS];
+        self.displayed = [b' '; MAX_CELLS];
+        self.dirty = None;
+        self.cursor_col = 0;
+        self.cursor_row = 0;
+        self.hw_cursor = Some(0);
+        Ok(())
+    }
+
+    /// Move the cursor to `(col, row)`, both zero-indexed.
+    ///
+    /// When [`GroveLcd::auto_flush`] is disabled this only records the
+    /// position for the next buffered `write`/`print`; the hardware cursor is
+    /// repositioned lazily by [`GroveLcd::flush`].
+    pub fn set_cursor(&mut self, col: u8, row: u8) -> Result<(), Error<I2C::Error>> {
+        self.cursor_col = col;
+        self.cursor_row = row;
+        if self.auto_flush {
+            self.move_hw_cursor()?;
+        }
+        Ok(())
+    }
+
+    /// Return the cursor to the home position without clearing the display.
+    pub fn home(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.send_command(0x02)?;
+        self.delay.delay_ms(2);
+        self.cursor_col = 0;
+        self.cursor_row = 0;
+        self.hw_cursor = Some(0);
+        Ok(())
+    }
+
+    /// Turn the display on, keeping the current cursor/blink settings.
+    pub fn display_on(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.display_control |= DISPLAY_ON;
+        self.send_display_control()
+    }
+
+    /// Turn the display off without losing its contents.
+    pub fn display_off(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.display_control &= !DISPLAY_ON;
+        self.send_display_control()
+    }
+
+    /// Show the cursor at the current position.
+    pub fn cursor_on(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.display_control |= CURSOR_ON;
+        self.send_display_control()
+    }
+
+    /// Hide the cursor.
+    pub fn cursor_off(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.display_control &= !CURSOR_ON;
+        self.send_display_control()
+    }
+
+    /// Make the character at the cursor position blink.
+    pub fn blink_on(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.display_control |= BLINK_ON;
+        self.send_display_control()
+    }
+
+    /// Stop the cursor from blinking.
+    pub fn blink_off(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.display_control &= !BLINK_ON;
+        self.send_display_control()
+    }
+
+    /// Shift the whole display one position to the left, leaving the cursor
+    /// position in the data unchanged.
+    pub fn scroll_display_left(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.send_command(0x10 | (1 << 3))
+    }
+
+    /// Shift the whole display one position to the right.
+    pub fn scroll_display_right(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.send_command(0x10 | (1 << 3) | (1 << 2))
+    }
+
+    /// Set the text direction so new characters are written left-to-right.
+    pub fn left_to_right(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.entry_mode |= ENTRY_INCREMENT;
+        self.send_entry_mode()
+    }
+
+    /// Set the text direction so new characters are written right-to-left.
+    pub fn right_to_left(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.entry_mode &= !ENTRY_INCREMENT;
+        self.send_entry_mode()
+    }
+
+    /// Enable or disable autoscroll, which shifts the whole display (instead
+    /// of the cursor) as each character is written.
+    pub fn autoscroll(&mut self, on: bool) -> Result<(), Error<I2C::Error>> {
+        if on {
+            self.entry_mode |= ENTRY_SHIFT;
+        } else {
+            self.entry_mode &= !ENTRY_SHIFT;
+        }
+        self.send_entry_mode()
+    }
+
+    fn send_display_control(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.send_command(0x08 | self.display_control)
+    }
+
+    fn send_entry_mode(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.send_command(0x04 | self.entry_mode)
+    }
+
+    /// Define a custom 5x8 glyph at CGRAM `location` (0..=7), which can then
+    /// be printed with `write(location)`.
+    ///
+    /// Each entry in `bitmap` is one pixel row, using the low 5 bits.
+    /// Restores the DDRAM address afterwards so a following `print` lands
+    /// back at the cursor position in effect before the call.
+    pub fn create_char(&mut self, location: u8, bitmap: [u8; 8]) -> Result<(), Error<I2C::Error>> {
+        let location = location & 0x07;
+        self.send_command(0x40 | (location << 3))?;
+        for row in bitmap {
+            self.send_data(row & 0x1F)?;
+        }
+        // CGRAM writing always leaves DDRAM addressing in a CGRAM-adjacent
+        // state, so restore it directly regardless of `auto_flush`.
+        self.move_hw_cursor()
+    }
+
+    /// Print `text` at the current cursor position.
+    pub fn print(&mut self, text: &str) -> Result<(), Error<I2C::Error>> {
+        for b in text.bytes() {
+            self.write(b)?;
+        }
+        Ok(())
+    }
+
+    /// Write a single raw byte to the data register at the current cursor
+    /// position, advancing the cursor.
+    ///
+    /// When [`GroveLcd::auto_flush`] is disabled this only updates the
+    /// shadow buffer; call [`GroveLcd::flush`] to push pending changes.
+    pub fn write(&mut self, data: u8) -> Result<(), Error<I2C::Error>> {
+        let idx = self.cell_index(self.cursor_col, self.cursor_row);
+        if let Some(idx) = idx {
+            self.shadow[idx] = data;
+        }
+
+        if self.auto_flush {
+            let addr = self.ddram_addr(self.cursor_col, self.cursor_row);
+            if self.hw_cursor != Some(addr) {
+                self.send_command(0x80 | addr)?;
+            }
+            self.send_data(data)?;
+            if let Some(idx) = idx {
+                self.displayed[idx] = data;
+            }
+            self.hw_cursor = Some(addr.wrapping_add(1));
+        } else if let Some(idx) = idx {
+            self.mark_dirty(idx);
+        }
+
+        self.advance_cursor();
+        Ok(())
+    }
+
+    /// Toggle whether `print`/`write`/`set_cursor` write straight through to
+    /// the display (the default) or only update the shadow buffer until
+    /// [`GroveLcd::flush`] is called. Turning it back on flushes any pending
+    /// changes immediately.
+    pub fn auto_flush(&mut self, on: bool) -> Result<(), Error<I2C::Error>> {
+        self.auto_flush = on;
+        if on {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Push any buffered changes to the display.
+    ///
+    /// Walks the shadow buffer and batches each contiguous run of changed
+    /// cells within a row into a single I2C write, repositioning the cursor
+    /// only when the next run doesn't pick up where the hardware cursor left
+    /// off.
+    pub fn flush(&mut self) -> Result<(), Error<I2C::Error>> {
+        let (start, end) = match self.dirty {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+        let cols = self.cols.max(1) as usize;
+
+        let mut i = start;
+        while i <= end {
+            if self.shadow[i] == self.displayed[i] {
+                i += 1;
+                continue;
+            }
+
+            let (col, row) = self.cell_coords(i);
+            let addr = self.ddram_addr(col, row);
+            if self.hw_cursor != Some(addr) {
+                self.send_command(0x80 | addr)?;
+            }
+
+            let row_end = (row as usize + 1) * cols - 1;
+            let run_start = i;
+            while i <= end && i <= row_end && self.shadow[i] != self.displayed[i] {
+                i += 1;
+            }
+
+            let run_len = i - run_start;
+            let mut frame = [0u8; MAX_CELLS + 1];
+            frame[0] = DATA_PREFIX;
+            frame[1..1 + run_len].copy_from_slice(&self.shadow[run_start..i]);
+            self.i2c.write(LCD_ADDR, &frame[..1 + run_len])?;
+
+            self.displayed[run_start..i].copy_from_slice(&self.shadow[run_start..i]);
+            self.hw_cursor = Some(addr + run_len as u8);
+        }
+
+        self.dirty = None;
+        Ok(())
+    }
+
+    /// Linear shadow-buffer index `row * cols + col` for `(col, row)`, or
+    /// `None` if that index is past the `MAX_CELLS` entries of the
+    /// framebuffer. A `col` at or beyond `cols` is not rejected and maps onto
+    /// a cell of a later row.
+    fn cell_index(&self, col: u8, row: u8) -> Option<usize> {
+        let idx = row as usize * self.cols.max(1) as usize + col as usize;
+        (idx < MAX_CELLS).then_some(idx)
+    }
+
+    /// Inverse of [`GroveLcd::cell_index`].
+    fn cell_coords(&self, idx: usize) -> (u8, u8) {
+        let cols = self.cols.max(1) as usize;
+        ((idx % cols) as u8, (idx / cols) as u8)
+    }
+
+    /// DDRAM address for `(col, row)`, using the standard HD44780 row offsets
+    /// (`0x00`, `0x40`, `cols`, `0x40 + cols`) so 4-line displays address
+    /// correctly instead of aliasing rows 0/2 and 1/3.
+    fn ddram_addr(&self, col: u8, row: u8) -> u8 {
+        let row_offsets = [0x00, 0x40, self.cols, 0x40 + self.cols];
+        let offset = row_offsets[(row as usize).min(row_offsets.len() - 1)];
+        offset.wrapping_add(col)
+    }
+
+    /// Move the hardware cursor to `(cursor_col, cursor_row)` and record it.
+    fn move_hw_cursor(&mut self) -> Result<(), Error<I2C::Error>> {
+        let addr = self.ddram_addr(self.cursor_col, self.cursor_row);
+        self.send_command(0x80 | addr)?;
+        self.hw_cursor = Some(addr);
+        Ok(())
+    }
+
+    /// Advance the virtual cursor by one cell, wrapping to the next row.
+    ///
+    /// Guards against overflow/out-of-range `cursor_col`/`cursor_row` (e.g.
+    /// from a caller passing an out-of-range column to [`GroveLcd::set_cursor`])
+    /// instead of panicking on overflow-checked builds.
+    fn advance_cursor(&mut self) {
+        let rows = self.rows.max(1);
+        let next_col = self.cursor_col.saturating_add(1);
+        if next_col < self.cols {
+            self.cursor_col = next_col;
+        } else {
+            self.cursor_col = 0;
+            self.cursor_row = (self.cursor_row % rows).wrapping_add(1) % rows;
+        }
+    }
+
+    /// Extend the pending dirty range to include `idx`.
+    fn mark_dirty(&mut self, idx: usize) {
+        self.dirty = Some(match self.dirty {
+            Some((lo, hi)) => (lo.min(idx), hi.max(idx)),
+            None => (idx, idx),
+        });
+    }
+
+    /// Set the RGB backlight color.
+    pub fn set_rgb(&mut self, r: u8, g: u8, b: u8) -> Result<(), Error<I2C::Error>> {
+        match self.rgb_variant {
+            Some(RgbVariant::V5) => {
+                self.i2c.write(RGB_ADDR_V5, &[0x06, r])?;
+                self.i2c.write(RGB_ADDR_V5, &[0x07, g])?;
+                self.i2c.write(RGB_ADDR_V5, &[0x08, b])?;
+            }
+            // Default to v4 registers when the variant hasn't been detected
+            // yet (i.e. `set_rgb` called before `begin`).
+            Some(RgbVariant::V4) | None => {
+                self.i2c.write(RGB_ADDR_V4, &[0x04, r])?;
+                self.i2c.write(RGB_ADDR_V4, &[0x03, g])?;
+                self.i2c.write(RGB_ADDR_V4, &[0x02, b])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Blink the whole backlight in hardware, with no CPU involvement once
+    /// set up.
+    ///
+    /// `duty_percent` (0..=100) is the fraction of each `period_ms` the
+    /// backlight spends on. On v5 boards, which have no hardware group-blink,
+    /// this is approximated by driving the PWM register at the average duty.
+    pub fn set_backlight_blink(
+        &mut self,
+        period_ms: u32,
+        duty_percent: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        let grppwm = (duty_percent.min(100) as u16 * 255 / 100) as u8;
+        match self.rgb_variant {
+            Some(RgbVariant::V5) => {
+                self.i2c.write(RGB_ADDR_V5, &[V5_REG_PWM, grppwm])?;
+            }
+            Some(RgbVariant::V4) | None => {
+                let grpfreq = Self::period_to_grpfreq(period_ms);
+                self.i2c
+                    .write(RGB_ADDR_V4, &[V4_REG_LEDOUT, LEDOUT_GROUP_CONTROLLED])?;
+                self.i2c
+                    .write(RGB_ADDR_V4, &[V4_REG_MODE2, MODE2_DMBLNK])?;
+                self.i2c.write(RGB_ADDR_V4, &[V4_REG_GRPFREQ, grpfreq])?;
+                self.i2c.write(RGB_ADDR_V4, &[V4_REG_GRPPWM, grppwm])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Smoothly fade the whole backlight in and out in hardware ("breathe"),
+    /// with no CPU involvement once set up.
+    ///
+    /// On v5 boards, which have no hardware group-dimming, this falls back to
+    /// the same steady output as [`GroveLcd::set_backlight_solid`].
+    pub fn set_backlight_breathe(&mut self, period_ms: u32) -> Result<(), Error<I2C::Error>> {
+        match self.rgb_variant {
+            Some(RgbVariant::V5) => {
+                self.i2c.write(RGB_ADDR_V5, &[V5_REG_PWM, V5_PWM_ALWAYS_ON])?;
+            }
+            Some(RgbVariant::V4) | None => {
+                let grpfreq = Self::period_to_grpfreq(period_ms);
+                self.i2c
+                    .write(RGB_ADDR_V4, &[V4_REG_LEDOUT, LEDOUT_GROUP_CONTROLLED])?;
+                self.i2c.write(RGB_ADDR_V4, &[V4_REG_MODE2, 0x00])?; // DMBLNK=0: group dimming
+                self.i2c.write(RGB_ADDR_V4, &[V4_REG_GRPFREQ, grpfreq])?;
+                self.i2c.write(RGB_ADDR_V4, &[V4_REG_GRPPWM, 0xFF])?; // full fade depth
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop any hardware blink/breathe effect and restore steady backlight
+    /// output.
+    pub fn set_backlight_solid(&mut self) -> Result<(), Error<I2C::Error>> {
+        match self.rgb_variant {
+            Some(RgbVariant::V5) => {
+                self.i2c.write(RGB_ADDR_V5, &[V5_REG_PWM, V5_PWM_ALWAYS_ON])?;
+            }
+            Some(RgbVariant::V4) | None => {
+                self.i2c.write(RGB_ADDR_V4, &[V4_REG_MODE2, 0x00])?;
+                self.i2c
+                    .write(RGB_ADDR_V4, &[V4_REG_LEDOUT, LEDOUT_INDIVIDUAL_PWM])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert a blink/breathe period in milliseconds to the PCA9633
+    /// `GRPFREQ` register value, where `period = (GRPFREQ + 1) / 24 s`.
+    fn period_to_grpfreq(period_ms: u32) -> u8 {
+        let scaled = (u64::from(period_ms) * 24 + 500) / 1000; // round to nearest
+        scaled.saturating_sub(1).min(255) as u8
+    }
+
+    fn send_command(&mut self, cmd: u8) -> Result<(), Error<I2C::Error>> {
+        self.i2c.write(LCD_ADDR, &[CMD_PREFIX, cmd])?;
+        Ok(())
+    }
+
+    fn send_data(&mut self, data: u8) -> Result<(), Error<I2C::Error>> {
+        self.i2c.write(LCD_ADDR, &[DATA_PREFIX, data])?;
+        Ok(())
+    }
+}
+
+impl<I2C, D> core::fmt::Write for GroveLcd<I2C, D>
+where
+    I2C: embedded_hal::i2c::I2c,
+    D: DelayNs,
+{
+    /// Forwards to [`GroveLcd::print`], mapping any I2C failure to
+    /// `core::fmt::Error` since `fmt::Write` carries no error payload.
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.print(s).map_err(|_| core::fmt::Error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    fn lcd(cols: u8, rows: u8, expectations: &[Transaction]) -> GroveLcd<Mock, NoopDelay> {
+        let mut lcd = GroveLcd::new(Mock::new(expectations), NoopDelay::new());
+        lcd.cols = cols;
+        lcd.rows = rows;
+        lcd
+    }
+
+    #[test]
+    fn ddram_addr_uses_the_real_four_line_offsets() {
+        let mut lcd = lcd(20, 4, &[]);
+        assert_eq!(lcd.ddram_addr(0, 0), 0x00);
+        assert_eq!(lcd.ddram_addr(0, 1), 0x40);
+        assert_eq!(lcd.ddram_addr(0, 2), 0x14);
+        assert_eq!(lcd.ddram_addr(0, 3), 0x54);
+        assert_eq!(lcd.ddram_addr(5, 2), 0x19);
+        lcd.i2c.done();
+    }
+
+    #[test]
+    fn flush_batches_runs_and_repositions_across_a_row_wrap() {
+        let mut lcd = lcd(
+            16,
+            2,
+            &[
+                Transaction::write(LCD_ADDR, [0x80, 0x8E].to_vec()),
+                Transaction::write(LCD_ADDR, [0x40, b'A', b'B'].to_vec()),
+                Transaction::write(LCD_ADDR, [0x80, 0xC0].to_vec()),
+                Transaction::write(LCD_ADDR, [0x40, b'C', b'D'].to_vec()),
+            ],
+        );
+        lcd.auto_flush = false;
+        lcd.set_cursor(14, 0).unwrap();
+        lcd.print("ABCD").unwrap();
+        lcd.flush().unwrap();
+        lcd.i2c.done();
+    }
+
+    #[test]
+    fn advance_cursor_saturates_instead_of_overflowing() {
+        let mut lcd = lcd(16, 2, &[]);
+        lcd.cursor_col = 255;
+        lcd.cursor_row = 0;
+        lcd.advance_cursor();
+        assert_eq!(lcd.cursor_col, 0);
+        assert_eq!(lcd.cursor_row, 1);
+        lcd.i2c.done();
+    }
+}